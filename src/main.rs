@@ -1,12 +1,22 @@
-use std::{time::Instant, collections::HashSet};
+use std::{time::Instant, collections::HashSet, fs, fs::File, borrow::Cow};
 
 use macroquad::prelude::*;
+use macroquad::ui::{hash, root_ui, widgets::Window};
+use serde::{Serialize, Deserialize};
 
-/// FPS for the simulation
+/// path circuits are saved to and loaded from
+const SAVE_PATH: &str = "circuit.ww";
+/// path simulation recordings are written to
+const GIF_PATH: &str = "recording.gif";
+/// integer factor each cell is upscaled by in recorded frames
+const GIF_SCALE: usize = 4;
+
+/// default simulation speed in generations per second, used as the starting
+/// value for the adjustable speed control
 const FPS: f32 = 2.0;
-/// target time in seconds 
-/// between each simulation frame
-const FPS_TIME: f32 = 1.0 / FPS;
+/// how much of each frame turbo mode spends advancing the simulation before
+/// yielding so the window stays responsive
+const TURBO_BUDGET: f32 = 1.0 / 60.0;
 
 fn window_conf() -> Conf {
     Conf {
@@ -19,7 +29,7 @@ fn window_conf() -> Conf {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 enum Cell {
     Empty,
     Head,
@@ -27,6 +37,80 @@ enum Cell {
     Conductor,
 }
 
+/// on-disk circuit format: board dimensions plus a run-length-encoded cell
+/// stream, kept compact since empty cells dominate most designs
+#[derive(Serialize, Deserialize)]
+struct SaveFile {
+    width: usize,
+    height: usize,
+    runs: Vec<(u32, Cell)>,
+}
+
+/// the active drawing tool used when painting on the board
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Tool {
+    Brush,
+    Line,
+    Rectangle,
+    Fill,
+}
+
+/// a rectangular snapshot of board cells, copied from a selection and stamped
+/// back elsewhere, optionally flipped or rotated
+struct Clipboard {
+    w: usize,
+    h: usize,
+    cells: Vec<Cell>,
+}
+
+impl Clipboard {
+    /// flips the contents horizontally, reversing the column order of each row
+    fn flip_horizontal(&mut self) {
+        for row in self.cells.chunks_mut(self.w) {
+            row.reverse();
+        }
+    }
+
+    /// rotates the contents 90° clockwise, swapping the dimensions and
+    /// remapping every index accordingly
+    fn rotate_cw(&mut self) {
+        let mut rotated = Vec::with_capacity(self.cells.len());
+        for x in 0..self.w {
+            for y in (0..self.h).rev() {
+                rotated.push(self.cells[y * self.w + x]);
+            }
+        }
+        std::mem::swap(&mut self.w, &mut self.h);
+        self.cells = rotated;
+    }
+}
+
+/// a single cell change, storing the value before and after the edit
+struct ModifyRecord {
+    x: usize,
+    y: usize,
+    old: Cell,
+    new: Cell,
+}
+
+/// every cell touched by a single stroke (one mouse press to release)
+struct Operation(Vec<ModifyRecord>);
+
+/// holds the history of edits so strokes can be undone and redone
+struct UndoStack {
+    done: Vec<Operation>,
+    undone: Vec<Operation>,
+}
+
+impl UndoStack {
+    pub fn new() -> Self {
+        Self {
+            done: Vec::new(),
+            undone: Vec::new(),
+        }
+    }
+}
+
 impl Cell {
     /// returns the color of the cell
     pub fn get_cell_color(&self) -> Color {
@@ -56,6 +140,27 @@ struct Wireworld {
 
     elapsed: Instant,
 
+    undo_stack: UndoStack,
+    current_op: Option<Operation>,
+    current_op_index: std::collections::HashMap<(usize, usize), usize>,
+
+    tool: Tool,
+    last_board_pos: Option<(usize, usize)>,
+    anchor: Option<(usize, usize)>,
+    tool_cell: Option<Cell>,
+
+    recording: bool,
+    gif_encoder: Option<gif::Encoder<File>>,
+    last_capture: Instant,
+
+    sim_speed: f32,
+    turbo: bool,
+    step_once: bool,
+
+    selection: Option<(usize, usize, usize, usize)>,
+    select_anchor: Option<(usize, usize)>,
+    clipboard: Option<Clipboard>,
+    paste_mode: bool,
 }
 
 impl Wireworld {
@@ -76,6 +181,23 @@ impl Wireworld {
             board_image,
             board_texture,
             elapsed: Instant::now(),
+            undo_stack: UndoStack::new(),
+            current_op: None,
+            current_op_index: std::collections::HashMap::new(),
+            tool: Tool::Brush,
+            last_board_pos: None,
+            anchor: None,
+            tool_cell: None,
+            recording: false,
+            gif_encoder: None,
+            last_capture: Instant::now(),
+            sim_speed: FPS,
+            turbo: false,
+            step_once: false,
+            selection: None,
+            select_anchor: None,
+            clipboard: None,
+            paste_mode: false,
         }
     }
 
@@ -101,6 +223,13 @@ impl Wireworld {
         self.updates = next_updates;
     }
 
+    /// advances the simulation by one generation, also capturing a frame when
+    /// a recording is in progress
+    fn advance_generation(&mut self) {
+        self.next_generation();
+        self.maybe_capture_frame();
+    }
+
     /// returns the next state of a cell in any given position
     fn next_state(&self, x: usize, y: usize) -> Cell {
         let cell = self.board[y][x];
@@ -136,6 +265,12 @@ impl Wireworld {
 
     /// handles the input for panning and zooming
     fn handle_pan_and_zoom(&mut self) {
+        // let Ctrl shortcuts (save/load/copy/…) take the key without also
+        // scrolling the viewport through the shared WASD bindings
+        if is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl) {
+            return;
+        }
+
         if is_key_down(KeyCode::A) {
             self.x_offset -= 10.0 / self.scale;
         } else if is_key_down(KeyCode::D) {
@@ -169,26 +304,208 @@ impl Wireworld {
     fn handle_mouse_input(&mut self) {
         let (m_x, m_y) = mouse_position();
 
-        if is_mouse_button_down(MouseButton::Left) {
-            let (board_x, board_y) = self.screen_to_board_rounded(m_x, m_y);
-            if board_x >= 0 && board_x < self.width as isize && board_y >= 0 && board_y < self.height as isize {
-                self.insert_cell(Cell::Conductor, board_x as usize, board_y as usize);
+        // clicks over the control panel belong to the UI, not the board
+        if Self::controls_rect().contains(vec2(m_x, m_y)) {
+            return;
+        }
+
+        let (board_x, board_y) = self.screen_to_board_rounded(m_x, m_y);
+        let in_bounds = self.in_bounds(board_x, board_y);
+
+        // each mouse button paints a different cell variant
+        let pressed_cell = Self::cell_for_button(is_mouse_button_pressed);
+        let down_cell = Self::cell_for_button(is_mouse_button_down);
+        let released = is_mouse_button_released(MouseButton::Left)
+            || is_mouse_button_released(MouseButton::Right)
+            || is_mouse_button_released(MouseButton::Middle);
+
+        // stamping the clipboard takes over the left button entirely
+        if self.paste_mode {
+            if is_mouse_button_pressed(MouseButton::Left) && in_bounds {
+                self.stamp_clipboard(board_x as usize, board_y as usize);
+            }
+            return;
+        }
+
+        // holding the modifier turns the left button into a selection drag
+        // rather than a paint stroke
+        if is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift) {
+            if is_mouse_button_pressed(MouseButton::Left) && in_bounds {
+                self.select_anchor = Some((board_x as usize, board_y as usize));
+                self.selection = Some((board_x as usize, board_y as usize, board_x as usize, board_y as usize));
+            } else if is_mouse_button_down(MouseButton::Left) {
+                if let Some((ax, ay)) = self.select_anchor {
+                    let (cx, cy) = self.clamp_to_board(board_x, board_y);
+                    self.selection = Some((ax, ay, cx as usize, cy as usize));
+                }
+            } else {
+                self.select_anchor = None;
+            }
+            return;
+        }
+
+        match self.tool {
+            Tool::Brush => {
+                if let Some(cell) = down_cell {
+                    self.begin_operation();
+                    if in_bounds {
+                        match self.last_board_pos {
+                            // interpolate fast drags so they leave no gaps
+                            Some((lx, ly)) => self.paint_line(lx as isize, ly as isize, board_x, board_y, cell),
+                            None => self.insert_cell(cell, board_x as usize, board_y as usize),
+                        }
+                        self.last_board_pos = Some((board_x as usize, board_y as usize));
+                    }
+                } else {
+                    self.end_operation();
+                    self.last_board_pos = None;
+                }
+            }
+            Tool::Line | Tool::Rectangle => {
+                if let Some(cell) = pressed_cell {
+                    if in_bounds {
+                        self.begin_operation();
+                        self.anchor = Some((board_x as usize, board_y as usize));
+                        self.tool_cell = Some(cell);
+                    }
+                } else if released {
+                    if let (Some((ax, ay)), Some(cell)) = (self.anchor, self.tool_cell) {
+                        let (cx, cy) = self.clamp_to_board(board_x, board_y);
+                        if self.tool == Tool::Line {
+                            self.paint_line(ax as isize, ay as isize, cx, cy, cell);
+                        } else {
+                            self.paint_rect(ax as isize, ay as isize, cx, cy, cell);
+                        }
+                        self.end_operation();
+                    }
+                    self.anchor = None;
+                    self.tool_cell = None;
+                }
+            }
+            Tool::Fill => {
+                if let Some(cell) = pressed_cell {
+                    if in_bounds {
+                        self.begin_operation();
+                        self.flood_fill(board_x as usize, board_y as usize, cell);
+                        self.end_operation();
+                    }
+                }
+            }
+        }
+    }
+
+    /// returns the cell variant a pressed/held mouse button paints, using the
+    /// supplied macroquad predicate (`is_mouse_button_pressed`/`_down`)
+    fn cell_for_button(pred: impl Fn(MouseButton) -> bool) -> Option<Cell> {
+        if pred(MouseButton::Left) {
+            Some(Cell::Conductor)
+        } else if pred(MouseButton::Right) {
+            Some(Cell::Empty)
+        } else if pred(MouseButton::Middle) {
+            Some(Cell::Head)
+        } else {
+            None
+        }
+    }
+
+    /// whether an integer board position lies within the grid
+    fn in_bounds(&self, x: isize, y: isize) -> bool {
+        x >= 0 && x < self.width as isize && y >= 0 && y < self.height as isize
+    }
+
+    /// clamps an integer board position to the nearest valid cell
+    fn clamp_to_board(&self, x: isize, y: isize) -> (isize, isize) {
+        (x.clamp(0, self.width as isize - 1), y.clamp(0, self.height as isize - 1))
+    }
+
+    /// the set of cells along a line using Bresenham's algorithm
+    fn bresenham(x0: isize, y0: isize, x1: isize, y1: isize) -> Vec<(isize, isize)> {
+        let mut cells = Vec::new();
+
+        let dx = (x1 - x0).abs();
+        let dy = -(y1 - y0).abs();
+        let sx = if x0 < x1 { 1 } else { -1 };
+        let sy = if y0 < y1 { 1 } else { -1 };
+        let mut err = dx + dy;
+
+        let (mut x, mut y) = (x0, y0);
+        loop {
+            cells.push((x, y));
+            if x == x1 && y == y1 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
             }
-        } else if is_mouse_button_down(MouseButton::Right) {
-            let (board_x, board_y) = self.screen_to_board_rounded(m_x, m_y);
-            if board_x >= 0 && board_x < self.width as isize && board_y >= 0 && board_y < self.height as isize {
-                self.insert_cell(Cell::Empty, board_x as usize, board_y as usize);
+            if e2 <= dx {
+                err += dx;
+                y += sy;
             }
-        } else if is_mouse_button_down(MouseButton::Middle) {
-            let (board_x, board_y) = self.screen_to_board_rounded(m_x, m_y);
-            if board_x >= 0 && board_x < self.width as isize && board_y >= 0 && board_y < self.height as isize {
-                self.insert_cell(Cell::Head, board_x as usize, board_y as usize);
+        }
+
+        cells
+    }
+
+    /// paints a Bresenham line between two board positions
+    fn paint_line(&mut self, x0: isize, y0: isize, x1: isize, y1: isize, cell: Cell) {
+        for (x, y) in Self::bresenham(x0, y0, x1, y1) {
+            if self.in_bounds(x, y) {
+                self.insert_cell(cell, x as usize, y as usize);
+            }
+        }
+    }
+
+    /// paints the outline of the rectangle spanned by two opposite corners
+    fn paint_rect(&mut self, x0: isize, y0: isize, x1: isize, y1: isize, cell: Cell) {
+        let (left, right) = (x0.min(x1), x0.max(x1));
+        let (top, bottom) = (y0.min(y1), y0.max(y1));
+        self.paint_line(left, top, right, top, cell);
+        self.paint_line(left, bottom, right, bottom, cell);
+        self.paint_line(left, top, left, bottom, cell);
+        self.paint_line(right, top, right, bottom, cell);
+    }
+
+    /// 4-connected flood fill from a start cell, replacing every contiguous
+    /// cell of the same variant with `cell` using an explicit stack
+    fn flood_fill(&mut self, x: usize, y: usize, cell: Cell) {
+        let target = self.board[y][x];
+        if target == cell {
+            return;
+        }
+
+        let mut stack = vec![(x, y)];
+        while let Some((cx, cy)) = stack.pop() {
+            if self.board[cy][cx] != target {
+                continue;
+            }
+            self.insert_cell(cell, cx, cy);
+            if cx > 0 {
+                stack.push((cx - 1, cy));
+            }
+            if cx < self.width - 1 {
+                stack.push((cx + 1, cy));
+            }
+            if cy > 0 {
+                stack.push((cx, cy - 1));
+            }
+            if cy < self.height - 1 {
+                stack.push((cx, cy + 1));
             }
         }
     }
 
     fn insert_cell(&mut self, cell: Cell, x: usize, y: usize) {
+        let old = self.board[y][x];
+        self.record_change(x, y, old, cell);
         self.board[y][x] = cell;
+        self.touch_updates(x, y);
+    }
+
+    /// re-inserts a cell and its 3×3 neighborhood into the active-update set
+    /// so the simulation texture and update bookkeeping stay consistent
+    fn touch_updates(&mut self, x: usize, y: usize) {
         self.updates.insert((x, y));
         for n_x in ((x as isize - 1).max(0) as usize)..=(x + 1).min(self.width - 1) {
             for n_y in ((y as isize - 1).max(0) as usize)..=(y + 1).min(self.height - 1) {
@@ -197,6 +514,329 @@ impl Wireworld {
         }
     }
 
+    /// accumulates a cell change into the in-progress operation, coalescing
+    /// repeated touches of the same cell so only the first `old` and last
+    /// `new` are kept
+    fn record_change(&mut self, x: usize, y: usize, old: Cell, new: Cell) {
+        if let Some(op) = &mut self.current_op {
+            // a position index keeps coalescing O(1) per cell so large fills
+            // and stamps stay linear in the number of touched cells
+            if let Some(&i) = self.current_op_index.get(&(x, y)) {
+                op.0[i].new = new;
+            } else {
+                self.current_op_index.insert((x, y), op.0.len());
+                op.0.push(ModifyRecord { x, y, old, new });
+            }
+        }
+    }
+
+    /// begins accumulating cell changes into a single operation
+    fn begin_operation(&mut self) {
+        if self.current_op.is_none() {
+            self.current_op = Some(Operation(Vec::new()));
+            self.current_op_index.clear();
+        }
+    }
+
+    /// commits the in-progress operation onto the done stack and clears the
+    /// redo history, dropping records that left a cell unchanged (and the whole
+    /// operation if nothing visibly changed) so every undo step is meaningful
+    fn end_operation(&mut self) {
+        if let Some(mut op) = self.current_op.take() {
+            op.0.retain(|record| record.old != record.new);
+            if !op.0.is_empty() {
+                self.undo_stack.done.push(op);
+                self.undo_stack.undone.clear();
+            }
+        }
+    }
+
+    /// reverts the most recent operation, writing each `old` value back and
+    /// re-inserting the affected neighborhoods into the update set
+    fn undo(&mut self) {
+        if let Some(op) = self.undo_stack.done.pop() {
+            for record in &op.0 {
+                self.board[record.y][record.x] = record.old;
+                self.touch_updates(record.x, record.y);
+            }
+            self.undo_stack.undone.push(op);
+        }
+    }
+
+    /// replays the most recently undone operation, writing each `new` value
+    /// back and re-inserting the affected neighborhoods into the update set
+    fn redo(&mut self) {
+        if let Some(op) = self.undo_stack.undone.pop() {
+            for record in &op.0 {
+                self.board[record.y][record.x] = record.new;
+                self.touch_updates(record.x, record.y);
+            }
+            self.undo_stack.done.push(op);
+        }
+    }
+
+    /// serializes the board to `SAVE_PATH` as a run-length-encoded `.ww` file
+    fn save_to_file(&self) {
+        let mut runs: Vec<(u32, Cell)> = Vec::new();
+        for row in &self.board {
+            for &cell in row {
+                match runs.last_mut() {
+                    Some((count, last)) if *last == cell => *count += 1,
+                    _ => runs.push((1, cell)),
+                }
+            }
+        }
+
+        let save = SaveFile {
+            width: self.width,
+            height: self.height,
+            runs,
+        };
+
+        match postcard::to_stdvec(&save) {
+            Ok(bytes) => {
+                if let Err(e) = fs::write(SAVE_PATH, bytes) {
+                    eprintln!("failed to save circuit: {e}");
+                }
+            }
+            Err(e) => eprintln!("failed to serialize circuit: {e}"),
+        }
+    }
+
+    /// loads a circuit from `SAVE_PATH`, reallocating the grid and texture if
+    /// the stored dimensions differ, and seeds `updates` with every non-empty
+    /// cell plus its neighborhood so the first generation behaves correctly
+    fn load_from_file(&mut self) {
+        let bytes = match fs::read(SAVE_PATH) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                eprintln!("failed to read circuit: {e}");
+                return;
+            }
+        };
+
+        let save: SaveFile = match postcard::from_bytes(&bytes) {
+            Ok(save) => save,
+            Err(e) => {
+                eprintln!("failed to deserialize circuit: {e}");
+                return;
+            }
+        };
+
+        // expand the run-length stream back into a flat cell list
+        let mut cells = Vec::with_capacity(save.width * save.height);
+        for (count, cell) in save.runs {
+            for _ in 0..count {
+                cells.push(cell);
+            }
+        }
+        if cells.len() != save.width * save.height {
+            eprintln!("corrupt circuit file: cell count mismatch");
+            return;
+        }
+
+        // rebuild the grid and a fresh black texture, reallocating when the
+        // stored dimensions differ from the current ones
+        self.board_image = Image::gen_image_color(save.width as u16, save.height as u16, BLACK);
+        self.board_texture = Texture2D::from_image(&self.board_image);
+        self.board_texture.set_filter(FilterMode::Nearest);
+        self.width = save.width;
+        self.height = save.height;
+        self.board = vec![vec![Cell::Empty; self.width]; self.height];
+
+        self.updates.clear();
+        self.undo_stack = UndoStack::new();
+        self.current_op = None;
+        self.current_op_index.clear();
+
+        // transient editor state refers to the old grid, so drop it rather than
+        // let a stale selection/clipboard index the resized board
+        self.selection = None;
+        self.select_anchor = None;
+        self.clipboard = None;
+        self.paste_mode = false;
+        self.anchor = None;
+        self.tool_cell = None;
+        self.last_board_pos = None;
+
+        for (i, cell) in cells.into_iter().enumerate() {
+            let x = i % self.width;
+            let y = i / self.width;
+            self.board[y][x] = cell;
+            if cell != Cell::Empty {
+                self.touch_updates(x, y);
+            }
+        }
+    }
+
+    /// starts or stops capturing the simulation to an animated GIF
+    fn toggle_recording(&mut self) {
+        if self.recording {
+            self.stop_recording();
+        } else {
+            self.start_recording();
+        }
+    }
+
+    /// opens the GIF encoder and writes the current board as the first frame.
+    /// frames are streamed straight to disk as they are captured rather than
+    /// buffered, so recordings of any length use constant memory
+    fn start_recording(&mut self) {
+        let file = match File::create(GIF_PATH) {
+            Ok(file) => file,
+            Err(e) => {
+                eprintln!("failed to create recording: {e}");
+                return;
+            }
+        };
+
+        let palette = Self::gif_palette();
+        let gif_width = (self.width * GIF_SCALE) as u16;
+        let gif_height = (self.height * GIF_SCALE) as u16;
+
+        let mut encoder = match gif::Encoder::new(file, gif_width, gif_height, &palette) {
+            Ok(encoder) => encoder,
+            Err(e) => {
+                eprintln!("failed to start recording encoder: {e}");
+                return;
+            }
+        };
+        if let Err(e) = encoder.set_repeat(gif::Repeat::Infinite) {
+            eprintln!("failed to configure recording: {e}");
+            return;
+        }
+
+        self.gif_encoder = Some(encoder);
+        self.recording = true;
+        self.last_capture = Instant::now();
+        self.capture_frame();
+    }
+
+    /// finalizes the recording; dropping the encoder flushes the GIF trailer
+    fn stop_recording(&mut self) {
+        self.recording = false;
+        self.gif_encoder = None;
+    }
+
+    /// the intended wall-clock time between generations at the current speed,
+    /// used both to pace the simulation-rate recording and to time GIF frames
+    fn sim_step_time(&self) -> f32 {
+        1.0 / self.sim_speed
+    }
+
+    /// captures a frame if one is due, throttled to at most one frame per
+    /// simulation step so turbo mode (hundreds of generations a second) can't
+    /// flood the encoder with near-identical frames while still tracking the
+    /// selected `sim_speed`
+    fn maybe_capture_frame(&mut self) {
+        if self.recording && self.last_capture.elapsed().as_secs_f32() >= self.sim_step_time() {
+            self.capture_frame();
+            self.last_capture = Instant::now();
+        }
+    }
+
+    /// the three-byte RGB palette holding the four fixed cell colors, indexed
+    /// by the `Cell` discriminant so a cell maps to its entry with an `as` cast
+    fn gif_palette() -> Vec<u8> {
+        let mut palette = Vec::with_capacity(4 * 3);
+        for cell in [Cell::Empty, Cell::Head, Cell::Tail, Cell::Conductor] {
+            let color = cell.get_cell_color();
+            palette.push((color.r * 255.0) as u8);
+            palette.push((color.g * 255.0) as u8);
+            palette.push((color.b * 255.0) as u8);
+        }
+        palette
+    }
+
+    /// writes the current board to the encoder as a palette-indexed frame, one
+    /// pixel per cell upscaled by `GIF_SCALE`, built from the board rather than
+    /// screen pixels so the output stays crisp and resolution-independent
+    fn capture_frame(&mut self) {
+        if self.gif_encoder.is_none() {
+            return;
+        }
+
+        let width = self.width * GIF_SCALE;
+        let mut buffer = Vec::with_capacity(width * self.height * GIF_SCALE);
+        for row in &self.board {
+            let mut line = Vec::with_capacity(width);
+            for &cell in row {
+                let index = cell as u8;
+                for _ in 0..GIF_SCALE {
+                    line.push(index);
+                }
+            }
+            for _ in 0..GIF_SCALE {
+                buffer.extend_from_slice(&line);
+            }
+        }
+
+        let gif_width = (self.width * GIF_SCALE) as u16;
+        let gif_height = (self.height * GIF_SCALE) as u16;
+        // gif delays are expressed in hundredths of a second; track the current
+        // speed so playback matches what was on screen, never below one tick
+        let delay = ((self.sim_step_time() * 100.0) as u16).max(1);
+        if let Some(encoder) = &mut self.gif_encoder {
+            let frame = gif::Frame {
+                width: gif_width,
+                height: gif_height,
+                delay,
+                buffer: Cow::Borrowed(&buffer),
+                ..Default::default()
+            };
+            if let Err(e) = encoder.write_frame(&frame) {
+                eprintln!("failed to write recording frame: {e}");
+            }
+        }
+    }
+
+    /// copies the current selection into the clipboard, row by row
+    fn copy_selection(&mut self) {
+        let Some((x0, y0, x1, y1)) = self.selection else {
+            return;
+        };
+
+        // clamp to the current grid so a stale or oversized selection can never
+        // index out of bounds
+        let left = x0.min(x1).min(self.width - 1);
+        let right = x0.max(x1).min(self.width - 1);
+        let top = y0.min(y1).min(self.height - 1);
+        let bottom = y0.max(y1).min(self.height - 1);
+        let w = right - left + 1;
+        let h = bottom - top + 1;
+
+        let mut cells = Vec::with_capacity(w * h);
+        for y in top..=bottom {
+            for x in left..=right {
+                cells.push(self.board[y][x]);
+            }
+        }
+
+        self.clipboard = Some(Clipboard { w, h, cells });
+    }
+
+    /// stamps the clipboard with its top-left corner at `(ox, oy)`, overwriting
+    /// the target area and feeding each changed cell through `insert_cell` so
+    /// the stamp is a single undoable operation
+    fn stamp_clipboard(&mut self, ox: usize, oy: usize) {
+        let Some(clipboard) = self.clipboard.take() else {
+            return;
+        };
+
+        self.begin_operation();
+        for i in 0..clipboard.h {
+            for j in 0..clipboard.w {
+                let (x, y) = (ox + j, oy + i);
+                if self.in_bounds(x as isize, y as isize) {
+                    self.insert_cell(clipboard.cells[i * clipboard.w + j], x, y);
+                }
+            }
+        }
+        self.end_operation();
+
+        self.clipboard = Some(clipboard);
+    }
+
     /// handles all forms of input the user can give
     fn handle_input(&mut self) {
         self.handle_mouse_input();
@@ -205,6 +845,57 @@ impl Wireworld {
         if is_key_pressed(KeyCode::Space) {
             self.paused = !self.paused;
         }
+
+        // toggle capturing the running simulation to an animated GIF
+        if is_key_pressed(KeyCode::R) {
+            self.toggle_recording();
+        }
+
+        // select the active drawing tool
+        if is_key_pressed(KeyCode::Key1) {
+            self.tool = Tool::Brush;
+        } else if is_key_pressed(KeyCode::Key2) {
+            self.tool = Tool::Line;
+        } else if is_key_pressed(KeyCode::Key3) {
+            self.tool = Tool::Rectangle;
+        } else if is_key_pressed(KeyCode::Key4) {
+            self.tool = Tool::Fill;
+        }
+
+        let ctrl = is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl);
+        if ctrl && is_key_pressed(KeyCode::Z) {
+            self.undo();
+        } else if ctrl && is_key_pressed(KeyCode::Y) {
+            self.redo();
+        } else if ctrl && is_key_pressed(KeyCode::S) {
+            self.save_to_file();
+        } else if ctrl && is_key_pressed(KeyCode::O) {
+            self.load_from_file();
+        } else if ctrl && is_key_pressed(KeyCode::C) {
+            self.copy_selection();
+        } else if ctrl && is_key_pressed(KeyCode::V) {
+            // only enter paste mode once there is something to stamp
+            if self.clipboard.is_some() {
+                self.paste_mode = true;
+            }
+        }
+
+        // leave paste mode without stamping
+        if self.paste_mode && is_key_pressed(KeyCode::Escape) {
+            self.paste_mode = false;
+        }
+
+        // flip/rotate the clipboard before stamping, since gadgets are reused
+        // in multiple orientations
+        if self.paste_mode {
+            if let Some(clipboard) = &mut self.clipboard {
+                if is_key_pressed(KeyCode::F) {
+                    clipboard.flip_horizontal();
+                } else if is_key_pressed(KeyCode::G) {
+                    clipboard.rotate_cw();
+                }
+            }
+        }
     }
 
     /// takes an x and y in screen space
@@ -273,7 +964,129 @@ impl Wireworld {
                 draw_line(left.0, left.1, right.0, right.1, grid_weight, GRAY);
             }
         }
-        
+
+        self.draw_tool_preview();
+        self.draw_selection();
+        self.draw_paste_preview();
+    }
+
+    /// outlines the current selection rectangle in board space
+    fn draw_selection(&self) {
+        let Some((x0, y0, x1, y1)) = self.selection else {
+            return;
+        };
+
+        let (left, right) = (x0.min(x1), x0.max(x1));
+        let (top, bottom) = (y0.min(y1), y0.max(y1));
+        let (sx, sy) = self.board_to_screen(left, top);
+        let w = (right - left + 1) as f32 * self.scale;
+        let h = (bottom - top + 1) as f32 * self.scale;
+
+        draw_rectangle_lines(sx, sy, w, h, 2.0, GREEN);
+    }
+
+    /// draws a translucent preview of the clipboard following the cursor while
+    /// in paste mode
+    fn draw_paste_preview(&self) {
+        if !self.paste_mode {
+            return;
+        }
+        let Some(clipboard) = &self.clipboard else {
+            return;
+        };
+
+        let (m_x, m_y) = mouse_position();
+        let (ox, oy) = self.screen_to_board_rounded(m_x, m_y);
+
+        for i in 0..clipboard.h {
+            for j in 0..clipboard.w {
+                let (x, y) = (ox + j as isize, oy + i as isize);
+                if !self.in_bounds(x, y) {
+                    continue;
+                }
+                let mut color = clipboard.cells[i * clipboard.w + j].get_cell_color();
+                color.a = 0.5;
+                let (sx, sy) = self.board_to_screen(x as usize, y as usize);
+                draw_rectangle(sx, sy, self.scale, self.scale, color);
+            }
+        }
+    }
+
+    /// draws a translucent preview of the line/rectangle being dragged
+    fn draw_tool_preview(&self) {
+        let (Some((ax, ay)), Some(cell)) = (self.anchor, self.tool_cell) else {
+            return;
+        };
+
+        let (m_x, m_y) = mouse_position();
+        let (cx, cy) = {
+            let (x, y) = self.screen_to_board_rounded(m_x, m_y);
+            self.clamp_to_board(x, y)
+        };
+
+        let preview = match self.tool {
+            Tool::Line => Self::bresenham(ax as isize, ay as isize, cx, cy),
+            Tool::Rectangle => {
+                let (left, right) = ((ax as isize).min(cx), (ax as isize).max(cx));
+                let (top, bottom) = ((ay as isize).min(cy), (ay as isize).max(cy));
+                let mut cells = Self::bresenham(left, top, right, top);
+                cells.extend(Self::bresenham(left, bottom, right, bottom));
+                cells.extend(Self::bresenham(left, top, left, bottom));
+                cells.extend(Self::bresenham(right, top, right, bottom));
+                cells
+            }
+            _ => return,
+        };
+
+        let mut color = cell.get_cell_color();
+        color.a = 0.5;
+        for (x, y) in preview {
+            if !self.in_bounds(x, y) {
+                continue;
+            }
+            let (sx, sy) = self.board_to_screen(x as usize, y as usize);
+            draw_rectangle(sx, sy, self.scale, self.scale, color);
+        }
+    }
+
+    /// the screen-space rectangle occupied by the control panel, shared by the
+    /// renderer and the click guard so the panel can't paint the board beneath
+    fn controls_rect() -> Rect {
+        Rect::new(10.0, screen_height() - 120.0, 320.0, 110.0)
+    }
+
+    /// draws the on-screen control bar, wiring its widgets to play/pause,
+    /// single-step, the speed slider, and the turbo toggle
+    fn draw_controls(&mut self) {
+        let rect = Self::controls_rect();
+        Window::new(hash!(), vec2(rect.x, rect.y), vec2(rect.w, rect.h))
+            .label("Controls")
+            .titlebar(true)
+            .ui(&mut root_ui(), |ui| {
+                if ui.button(None, if self.paused { "Play" } else { "Pause" }) {
+                    self.paused = !self.paused;
+                }
+                ui.same_line(0.0);
+                // stepping only advances the simulation while it is paused
+                if ui.button(None, "Step") && self.paused {
+                    self.step_once = true;
+                }
+                ui.same_line(0.0);
+                if ui.button(None, if self.turbo { "Turbo: on" } else { "Turbo: off" }) {
+                    self.turbo = !self.turbo;
+                }
+                ui.slider(hash!(), "gen/s", 1.0..60.0, &mut self.sim_speed);
+            });
+    }
+
+    /// draws a visible "REC" indicator in the corner while capturing
+    fn draw_recording_indicator(&self) {
+        if !self.recording {
+            return;
+        }
+
+        draw_circle(30.0, 90.0, 10.0, RED);
+        draw_text("REC", 50.0, 100.0, 40.0, RED);
     }
 
     /// updates the state of the world
@@ -292,12 +1105,29 @@ impl Wireworld {
 
         self.handle_input();
 
-        if self.elapsed.elapsed().as_secs_f32() >= FPS_TIME && !self.paused {
-            self.next_generation();
+        if self.turbo && !self.paused {
+            // run as many generations as fit in the frame budget instead of
+            // pacing one per step
+            let start = Instant::now();
+            loop {
+                self.advance_generation();
+                if start.elapsed().as_secs_f32() >= TURBO_BUDGET {
+                    break;
+                }
+            }
+            self.elapsed = Instant::now();
+        } else if self.step_once {
+            self.advance_generation();
+            self.step_once = false;
+            self.elapsed = Instant::now();
+        } else if self.elapsed.elapsed().as_secs_f32() >= 1.0 / self.sim_speed && !self.paused {
+            self.advance_generation();
             self.elapsed = Instant::now();
         }
 
         self.draw_board();
+        self.draw_recording_indicator();
+        self.draw_controls();
     }
 }
 